@@ -11,6 +11,8 @@ pub use ankurah::signals::{ReactObserver, StoreChangeCallback};
 #[derive(Model, Debug, Serialize, Deserialize)]
 pub struct User {
     pub display_name: String,
+    /// Argon2id PHC-encoded password hash (salt embedded).
+    pub password_hash: String,
 }
 
 // Room model - chat rooms
@@ -19,6 +21,18 @@ pub struct Room {
     pub name: String,
 }
 
+// Membership model - which users belong to which rooms
+#[derive(Model, Debug, Serialize, Deserialize)]
+pub struct Membership {
+    #[active_type(LWW)]
+    pub user: Ref<User>,
+    #[active_type(LWW)]
+    pub room: Ref<Room>,
+    pub joined_at: i64,
+    #[active_type(LWW)]
+    pub left: bool,
+}
+
 #[derive(Model, Debug, Serialize, Deserialize)]
 pub struct Message {
     #[active_type(LWW)]
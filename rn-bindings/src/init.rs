@@ -10,7 +10,15 @@ use once_cell::sync::OnceCell;
 use std::sync::Mutex;
 use tokio::runtime::Runtime;
 
-use crate::types::AnkurahError;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+use ankurah::Ref;
+use ankurah_rn_model::{Membership, MembershipView, MessageView, Room, RoomView, User, UserView};
+
+use crate::types::{
+    AnkurahError, MessageCursor, MessageItem, MessagePage, PageDirection, PresenceState, RoomItem,
+};
 
 type AnkurahNode = Node<SledStorageEngine, PermissiveAgent>;
 type WsClient = WebsocketClient<SledStorageEngine, PermissiveAgent>;
@@ -19,10 +27,35 @@ static RUNTIME: OnceCell<Runtime> = OnceCell::new();
 static NODE: Mutex<Option<AnkurahNode>> = Mutex::new(None);
 static WS_CLIENT: Mutex<Option<WsClient>> = Mutex::new(None);
 
+/// The active authenticated session, if a user is logged in. Holds the verified
+/// user id together with the live `Context` its writes go through, so a session
+/// is a real handle to the store and not merely an id string on disk.
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// An authenticated session: the logged-in user's id plus the `Context` that
+/// attributes their reads and writes.
+struct Session {
+    user_id: String,
+    context: ankurah::Context,
+}
+
+/// Global connection-state callback for forwarding state to JS.
+static CONNECTION_CALLBACK: Mutex<Option<Box<dyn ConnectionStateCallback>>> = Mutex::new(None);
+
+/// Marks the reconnection supervisor as started, so `init_node` stays idempotent
+/// and we never spawn a second supervisor for the same process.
+static SUPERVISOR: OnceCell<()> = OnceCell::new();
+
 thread_local! {
     static ENTER_GUARD: std::cell::RefCell<Option<tokio::runtime::EnterGuard<'static>>> = const { std::cell::RefCell::new(None) };
 }
 
+/// Runtime handle, once `init_runtime` has built it. Used by layers (e.g. the
+/// OTLP exporter) that must be installed from within the Tokio context.
+pub(crate) fn runtime() -> Option<&'static Runtime> {
+    RUNTIME.get()
+}
+
 fn storage_path() -> PathBuf {
     dirs::data_local_dir()
         .map(|d| d.join("ankurah"))
@@ -30,6 +63,111 @@ fn storage_path() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from("ankurah_data"))
 }
 
+/// Default storage path for the current platform, as a string for JS.
+#[uniffi::export]
+pub fn get_default_storage_path() -> String {
+    storage_path().to_string_lossy().to_string()
+}
+
+// =============================================================================
+// Connection state - reports WebSocket lifecycle to JS
+// =============================================================================
+
+/// Lifecycle states of the connection to the sync server.
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum ConnectionState {
+    /// First connection attempt in progress.
+    Connecting,
+    /// Connected and the system is ready.
+    Connected,
+    /// Disconnected; retrying, `attempt` is the 1-based retry count.
+    Reconnecting { attempt: u32 },
+    /// Connection lost and not currently retrying.
+    Offline,
+}
+
+/// Callback interface for receiving connection-state changes in JS,
+/// so the UI can show a sync-status banner.
+#[uniffi::export(callback_interface)]
+pub trait ConnectionStateCallback: Send + Sync {
+    fn on_state_change(&self, state: ConnectionState);
+}
+
+/// Register the callback that receives connection-state changes.
+/// Should be called once at app startup before init_node.
+#[uniffi::export]
+pub fn set_connection_state_callback(callback: Box<dyn ConnectionStateCallback>) {
+    *CONNECTION_CALLBACK.lock().unwrap() = Some(callback);
+}
+
+fn emit_connection_state(state: ConnectionState) {
+    if let Some(cb) = CONNECTION_CALLBACK.lock().unwrap().as_ref() {
+        cb.on_state_change(state);
+    }
+}
+
+/// Backoff for retry `attempt` (1-based): 250ms doubling, capped at 30s, with
+/// jitter so a fleet of clients doesn't reconnect in lockstep.
+fn backoff_delay(attempt: u32) -> std::time::Duration {
+    const BASE_MS: u64 = 250;
+    const CAP_MS: u64 = 30_000;
+    let exp = BASE_MS.saturating_mul(1u64 << attempt.saturating_sub(1).min(7));
+    let base = exp.min(CAP_MS);
+    // Cheap, dependency-free jitter: up to ±12.5% from the current clock nanos.
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = (base / 8).max(1);
+    let offset = nanos % (2 * jitter + 1);
+    std::time::Duration::from_millis(base.saturating_sub(jitter) + offset)
+}
+
+/// Attach the presence receive handler to a freshly-connected client, so
+/// inbound ephemeral beacons are delivered to `handle_presence_beacon`.
+fn wire_client(client: &WsClient) {
+    client.on_ephemeral(|bytes: Vec<u8>| handle_presence_beacon(&bytes));
+}
+
+/// Supervise an already-connected node: hold the live client until its
+/// connection closes, then reconnect with exponential backoff, re-arm the
+/// presence handler, and report every transition through the state callback.
+///
+/// `client` is the connected client handed off by `init_node`; `WS_CLIENT` holds
+/// a clone for presence sends. Every reconnect attempt is preceded by a backoff
+/// sleep, so a server that refuses connections can never turn this into a
+/// busy-loop.
+async fn supervise_connection(node: AnkurahNode, url: String, mut client: WsClient) {
+    loop {
+        // Block until the underlying connection drops, then tear down the
+        // stored client and reconnect.
+        client.wait_for_close().await;
+        *WS_CLIENT.lock().unwrap() = None;
+        emit_connection_state(ConnectionState::Offline);
+
+        let mut attempt: u32 = 1;
+        loop {
+            emit_connection_state(ConnectionState::Reconnecting { attempt });
+            tokio::time::sleep(backoff_delay(attempt)).await;
+
+            match WebsocketClient::new(node.clone(), &url).await {
+                Ok(fresh) => {
+                    node.system.wait_system_ready().await;
+                    wire_client(&fresh);
+                    *WS_CLIENT.lock().unwrap() = Some(fresh.clone());
+                    emit_connection_state(ConnectionState::Connected);
+                    client = fresh;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!("reconnect attempt {} failed: {}", attempt, e);
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+        }
+    }
+}
+
 /// Initialize tokio runtime and enter context for this thread.
 /// Must be called before init_node().
 #[uniffi::export]
@@ -73,11 +211,20 @@ pub async fn init_node(server_url: Option<String>) -> Result<(), AnkurahError> {
         Some(url) => {
             let node = Node::new(storage, PermissiveAgent::new());
             tracing::info!("Node {}: connecting to {}", node.id, url);
+            emit_connection_state(ConnectionState::Connecting);
             let client = WebsocketClient::new(node.clone(), url)
                 .await
                 .map_err(|e| AnkurahError::Connection { message: e.to_string() })?;
             node.system.wait_system_ready().await;
-            *WS_CLIENT.lock().unwrap() = Some(client);
+            wire_client(&client);
+            *WS_CLIENT.lock().unwrap() = Some(client.clone());
+            emit_connection_state(ConnectionState::Connected);
+
+            // Hand the connected client to the supervisor, which holds it until
+            // it closes and then reconnects. Spawn exactly once per process.
+            if SUPERVISOR.set(()).is_ok() {
+                tokio::spawn(supervise_connection(node.clone(), url.clone(), client));
+            }
             node
         }
         None => {
@@ -100,11 +247,23 @@ pub async fn init_node(server_url: Option<String>) -> Result<(), AnkurahError> {
         if server_url.is_some() { "connected" } else { "offline" }
     );
 
+    // Resume a previously logged-in session, if one was persisted, rebuilding a
+    // live context for it rather than trusting the id string alone.
+    if let Ok(id) = std::fs::read_to_string(session_path()) {
+        let id = id.trim().to_string();
+        if !id.is_empty() {
+            tracing::info!("Resuming session for user {}", id);
+            set_session(&id)?;
+        }
+    }
+
     Ok(())
 }
 
-#[uniffi::export]
-pub fn get_context() -> Result<ankurah::Context, AnkurahError> {
+/// Build an unauthenticated context under the open default policy. Used only
+/// before a user logs in (the duplicate-name check in `register` and the user
+/// lookup in `login`); authenticated access goes through `authenticated_context`.
+fn build_context() -> Result<ankurah::Context, AnkurahError> {
     NODE.lock()
         .unwrap()
         .as_ref()
@@ -114,3 +273,558 @@ pub fn get_context() -> Result<ankurah::Context, AnkurahError> {
             message: e.to_string(),
         })
 }
+
+/// Build a context bound to `user_id` as its identity, so the node attributes
+/// the session's reads and writes to that authenticated principal instead of the
+/// open `DEFAULT_CONTEXT`. The id is resolved to the same `Ref<User>` the write
+/// paths use (`Ref: TryFrom<&str>`), which is the identity the context carries.
+fn authenticated_context(user_id: &str) -> Result<ankurah::Context, AnkurahError> {
+    let identity: Ref<User> = user_id.try_into().map_err(|_| AnkurahError::Auth {
+        message: "invalid user id".to_string(),
+    })?;
+    NODE.lock()
+        .unwrap()
+        .as_ref()
+        .ok_or(AnkurahError::NotInitialized)?
+        .context(identity)
+        .map_err(|e| AnkurahError::Internal {
+            message: e.to_string(),
+        })
+}
+
+/// Context for store access. Returns the authenticated session's context when a
+/// user is logged in, otherwise an unauthenticated context (used before login,
+/// e.g. the duplicate-name check in `register`).
+#[uniffi::export]
+pub fn get_context() -> Result<ankurah::Context, AnkurahError> {
+    if let Some(session) = SESSION.lock().unwrap().as_ref() {
+        return Ok(session.context.clone());
+    }
+    build_context()
+}
+
+/// Context that requires an authenticated session; errors if no user is logged
+/// in. The membership paths (join/leave and `fetch_my_rooms`) go through this so
+/// an unauthenticated caller can never mutate memberships.
+fn authed_context() -> Result<ankurah::Context, AnkurahError> {
+    SESSION
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|s| s.context.clone())
+        .ok_or(AnkurahError::Auth {
+            message: "not logged in".to_string(),
+        })
+}
+
+/// Whether `init_node` has successfully initialized a node.
+#[uniffi::export]
+pub fn is_node_initialized() -> bool {
+    NODE.lock().unwrap().is_some()
+}
+
+/// Stable id of the active node, or `None` if not yet initialized.
+#[uniffi::export]
+pub fn get_node_id() -> Option<String> {
+    NODE.lock().unwrap().as_ref().map(|n| n.id.to_string())
+}
+
+fn session_path() -> PathBuf {
+    storage_path().join("auth_session.txt")
+}
+
+/// Establish an authenticated session for `user_id`: build a context bound to
+/// that identity, hold it in memory, and persist the id so the session survives
+/// a restart. The session context is identity-scoped, not the open default one,
+/// so a successful login/register yields a real authenticated store handle.
+fn set_session(user_id: &str) -> Result<(), AnkurahError> {
+    let context = authenticated_context(user_id)?;
+    *SESSION.lock().unwrap() = Some(Session {
+        user_id: user_id.to_string(),
+        context,
+    });
+    let path = session_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    std::fs::write(&path, user_id).map_err(|e| AnkurahError::Storage {
+        message: format!("Failed to persist session: {}", e),
+    })
+}
+
+/// Escape a user-supplied value for interpolation into a query predicate.
+///
+/// Predicates are built with `format!`, so a raw single quote in a display name
+/// or id would terminate the literal and let the rest be parsed as query syntax.
+/// Doubling embedded quotes keeps the whole value a single string literal.
+fn escape_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+fn hash_password(password: &str) -> Result<String, AnkurahError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| AnkurahError::Auth { message: e.to_string() })
+}
+
+fn verify_password(password: &str, phc: &str) -> bool {
+    match PasswordHash::new(phc) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Register a new user with an Argon2id-hashed password and log them in.
+///
+/// Fails if the display name is already taken. On success the new user id is
+/// persisted as the active session and returned.
+#[uniffi::export]
+pub async fn register(display_name: String, password: String) -> Result<String, AnkurahError> {
+    let context = get_context()?;
+
+    let existing = context
+        .fetch::<UserView>(&format!("display_name = '{}'", escape_literal(&display_name)))
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    if !existing.is_empty() {
+        return Err(AnkurahError::Auth {
+            message: format!("display name '{}' is already taken", display_name),
+        });
+    }
+
+    let password_hash = hash_password(&password)?;
+
+    let trx = context.begin();
+    let user = trx
+        .create(&User { display_name, password_hash })
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    let id = user.id().to_string();
+    trx.commit()
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    set_session(&id)?;
+    Ok(id)
+}
+
+/// Log in by verifying `password` against the stored Argon2id PHC string.
+///
+/// On success the user id is persisted as the active session and returned.
+#[uniffi::export]
+pub async fn login(display_name: String, password: String) -> Result<String, AnkurahError> {
+    let context = get_context()?;
+
+    let users = context
+        .fetch::<UserView>(&format!("display_name = '{}'", escape_literal(&display_name)))
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    let user = users.first().ok_or(AnkurahError::Auth {
+        message: "invalid display name or password".to_string(),
+    })?;
+
+    let stored = user
+        .password_hash()
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    if !verify_password(&password, &stored) {
+        return Err(AnkurahError::Auth {
+            message: "invalid display name or password".to_string(),
+        });
+    }
+
+    let id = user.id().to_string();
+    set_session(&id)?;
+    Ok(id)
+}
+
+/// Id of the currently authenticated user, if a session is active.
+#[uniffi::export]
+pub fn current_user_id() -> Option<String> {
+    SESSION.lock().unwrap().as_ref().map(|s| s.user_id.clone())
+}
+
+fn require_user() -> Result<String, AnkurahError> {
+    current_user_id().ok_or(AnkurahError::Auth {
+        message: "not logged in".to_string(),
+    })
+}
+
+/// Join a room for the current user.
+///
+/// A user may hold only one active membership per room, so this is a no-op if a
+/// non-left membership already exists (mirrors the "check before insert" pattern).
+#[uniffi::export]
+pub async fn join_room(room_id: String) -> Result<(), AnkurahError> {
+    let user_id = require_user()?;
+    let context = authed_context()?;
+
+    let existing = context
+        .fetch::<MembershipView>(&format!(
+            "user = '{}' AND room = '{}' AND left = false",
+            escape_literal(&user_id),
+            escape_literal(&room_id)
+        ))
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    if !existing.is_empty() {
+        tracing::debug!("join_room: already a member of {}", room_id);
+        return Ok(());
+    }
+
+    let user_ref: Ref<User> = user_id
+        .as_str()
+        .try_into()
+        .map_err(|_| AnkurahError::Internal { message: "invalid user id".to_string() })?;
+    let room_ref: Ref<Room> = room_id
+        .as_str()
+        .try_into()
+        .map_err(|_| AnkurahError::Internal { message: "invalid room id".to_string() })?;
+    let joined_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+
+    let trx = context.begin();
+    trx.create(&Membership {
+        user: user_ref.into(),
+        room: room_ref.into(),
+        joined_at,
+        left: false,
+    })
+    .await
+    .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+    trx.commit()
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    Ok(())
+}
+
+/// Leave a room: mark the current user's active membership as `left`.
+#[uniffi::export]
+pub async fn leave_room(room_id: String) -> Result<(), AnkurahError> {
+    let user_id = require_user()?;
+    let context = authed_context()?;
+
+    let memberships = context
+        .fetch::<MembershipView>(&format!(
+            "user = '{}' AND room = '{}' AND left = false",
+            escape_literal(&user_id),
+            escape_literal(&room_id)
+        ))
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    let trx = context.begin();
+    for m in memberships.iter() {
+        m.edit(&trx)
+            .await
+            .map_err(|e| AnkurahError::Internal { message: e.to_string() })?
+            .left()
+            .set(true);
+    }
+    trx.commit()
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    Ok(())
+}
+
+/// List the rooms the current user has an active membership in.
+#[uniffi::export]
+pub async fn fetch_my_rooms() -> Result<Vec<RoomItem>, AnkurahError> {
+    let user_id = require_user()?;
+    let context = authed_context()?;
+
+    let memberships = context
+        .fetch::<MembershipView>(&format!("user = '{}' AND left = false", escape_literal(&user_id)))
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    let mut rooms = Vec::with_capacity(memberships.len());
+    for m in memberships.iter() {
+        let room_id = m
+            .room()
+            .map_err(|e| AnkurahError::Internal { message: e.to_string() })?
+            .id()
+            .to_string();
+        let found = context
+            .fetch::<RoomView>(&format!("id = '{}'", escape_literal(&room_id)))
+            .await
+            .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+        if let Some(room) = found.first() {
+            rooms.push(RoomItem {
+                id: room_id,
+                name: room.name().unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(rooms)
+}
+
+/// Callback interface for receiving live message changes in JS.
+///
+/// Modeled on `LogListener`: JS implements it, Rust invokes `on_change` for
+/// every change set produced by the live query. `removed` carries the ids of
+/// messages that dropped out of the result (e.g. soft-deleted).
+#[uniffi::export(callback_interface)]
+pub trait MessageSubscriber: Send + Sync {
+    fn on_change(&self, added: Vec<MessageItem>, updated: Vec<MessageItem>, removed: Vec<String>);
+}
+
+/// Handle to a live message subscription. Dropping it (or calling
+/// `unsubscribe`) tears down the underlying observer.
+#[derive(uniffi::Object)]
+pub struct MessageSubscription {
+    // Opaque guard kept alive for the lifetime of the subscription; dropping it
+    // unregisters the live query from the store.
+    guard: Mutex<Option<Box<dyn std::any::Any + Send + Sync>>>,
+}
+
+#[uniffi::export]
+impl MessageSubscription {
+    /// Explicitly tear down the subscription. Idempotent.
+    pub fn unsubscribe(&self) {
+        *self.guard.lock().unwrap() = None;
+    }
+}
+
+/// Open a live query over a room's messages and push every change set to `subscriber`.
+///
+/// Returns a handle that keeps the observer alive; drop it or call `unsubscribe`
+/// to stop receiving updates.
+#[uniffi::export]
+pub async fn subscribe_messages(
+    room_id: String,
+    subscriber: Box<dyn MessageSubscriber>,
+) -> Result<std::sync::Arc<MessageSubscription>, AnkurahError> {
+    let context = get_context()?;
+    let query = format!("room = '{}' AND deleted = false", escape_literal(&room_id));
+
+    let handle = context
+        .subscribe::<MessageView>(query.as_str(), move |changeset| {
+            let added = changeset.added().iter().map(message_item).collect();
+            let updated = changeset.updated().iter().map(message_item).collect();
+            let removed = changeset
+                .removed()
+                .iter()
+                .map(|m| m.id().to_string())
+                .collect();
+            subscriber.on_change(added, updated, removed);
+        })
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    Ok(std::sync::Arc::new(MessageSubscription {
+        guard: Mutex::new(Some(Box::new(handle))),
+    }))
+}
+
+// =============================================================================
+// Ephemeral presence and typing indicators
+//
+// Presence is deliberately NOT a CRDT record: it is broadcast as lightweight
+// ephemeral messages over the existing WebSocket and garbage-collected by TTL
+// on the receiving side, so stale "online"/"typing" state never lingers and
+// nothing ever touches Sled.
+// =============================================================================
+
+/// Callback interface for receiving presence updates in JS.
+///
+/// `expires_at_ms` is a wall-clock deadline; the receiver should drop the entry
+/// once it passes (we also GC it locally). Modeled on `LogListener`.
+#[uniffi::export(callback_interface)]
+pub trait PresenceCallback: Send + Sync {
+    fn on_presence(&self, user_id: String, room_id: String, state: PresenceState, expires_at_ms: i64);
+}
+
+static PRESENCE_CALLBACK: Mutex<Option<Box<dyn PresenceCallback>>> = Mutex::new(None);
+
+/// Received presence entries keyed by (user_id, room_id), retained until their
+/// TTL expires. Kept purely in memory.
+static PRESENCE: Mutex<Vec<PresenceEntry>> = Mutex::new(Vec::new());
+
+struct PresenceEntry {
+    user_id: String,
+    room_id: String,
+    state: PresenceState,
+    expires_at_ms: i64,
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Register the callback that receives presence updates from other users.
+#[uniffi::export]
+pub fn set_presence_callback(callback: Box<dyn PresenceCallback>) {
+    *PRESENCE_CALLBACK.lock().unwrap() = Some(callback);
+}
+
+/// Broadcast the current user's presence/typing state to a room.
+///
+/// Sends a short-lived ephemeral beacon over the WebSocket; it is never stored.
+#[uniffi::export]
+pub async fn set_presence(room_id: String, state: PresenceState) -> Result<(), AnkurahError> {
+    let user_id = require_user()?;
+    let expires_at_ms = now_ms() + state.ttl_ms();
+
+    // Wire format: presence|<user>|<room>|<state>|<expires_at_ms>
+    let payload = format!(
+        "presence|{}|{}|{}|{}",
+        user_id,
+        room_id,
+        state.as_tag(),
+        expires_at_ms
+    );
+
+    let guard = WS_CLIENT.lock().unwrap();
+    let client = guard.as_ref().ok_or(AnkurahError::NotInitialized)?;
+    client
+        .send_ephemeral(payload.into_bytes())
+        .await
+        .map_err(|e| AnkurahError::Connection { message: e.to_string() })
+}
+
+/// Handle an inbound ephemeral presence beacon: evict expired entries, record
+/// the update, and notify JS. Called by the WebSocket receive path.
+pub(crate) fn handle_presence_beacon(payload: &[u8]) {
+    let Ok(text) = std::str::from_utf8(payload) else { return };
+    let parts: Vec<&str> = text.split('|').collect();
+    if parts.len() != 5 || parts[0] != "presence" {
+        return;
+    }
+    let (user_id, room_id) = (parts[1].to_string(), parts[2].to_string());
+    let Some(state) = PresenceState::from_tag(parts[3]) else { return };
+    let Ok(expires_at_ms) = parts[4].parse::<i64>() else { return };
+
+    let now = now_ms();
+    {
+        let mut entries = PRESENCE.lock().unwrap();
+        // Garbage-collect anything that has outlived its TTL.
+        entries.retain(|e| e.expires_at_ms > now);
+        entries.retain(|e| !(e.user_id == user_id && e.room_id == room_id));
+        if expires_at_ms > now {
+            entries.push(PresenceEntry {
+                user_id: user_id.clone(),
+                room_id: room_id.clone(),
+                state,
+                expires_at_ms,
+            });
+        }
+    }
+
+    if let Some(cb) = PRESENCE_CALLBACK.lock().unwrap().as_ref() {
+        cb.on_presence(user_id, room_id, state, expires_at_ms);
+    }
+}
+
+/// Flatten a `MessageView` into the FFI-friendly `MessageItem` record.
+fn message_item(m: &MessageView) -> MessageItem {
+    MessageItem {
+        id: m.id().to_string(),
+        text: m.text().unwrap_or_default(),
+        timestamp_ms: m.timestamp().unwrap_or(0),
+    }
+}
+
+/// Fetch one window of a room's messages for infinite scroll.
+///
+/// The predicate language here is equality-only (see `print_all_messages` in the
+/// server, which fetches `room = ? AND deleted = false` and sorts in memory), so
+/// we select the room's live messages, impose the total order `(timestamp, id)`
+/// in memory — deterministic even when timestamps collide — then window:
+/// - `Latest` returns the last `limit` rows (cursor ignored).
+/// - `Before`/`After` return up to `limit` rows strictly before/after the cursor.
+/// - `Around` centers on (and includes) the cursor row, half-before/half-after.
+///
+/// `has_more` reports whether further rows exist in the scroll direction, and
+/// `start`/`end` carry the cursors of the returned window so JS can request the
+/// adjacent page without re-fetching the whole room.
+#[uniffi::export]
+pub async fn fetch_message_page(
+    room_id: String,
+    direction: PageDirection,
+    cursor: Option<MessageCursor>,
+    limit: u32,
+) -> Result<MessagePage, AnkurahError> {
+    let context = get_context()?;
+
+    let query = format!("room = '{}' AND deleted = false", escape_literal(&room_id));
+    let messages = context
+        .fetch::<MessageView>(query.as_str())
+        .await
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    // Establish a stable total order so colliding timestamps never reorder.
+    let mut rows: Vec<MessageItem> = messages.iter().map(message_item).collect();
+    rows.sort_by(|a, b| {
+        a.timestamp_ms
+            .cmp(&b.timestamp_ms)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let limit = (limit as usize).max(1);
+    let anchor = cursor.as_ref().map(|c| (c.timestamp_ms, c.message_id.as_str()));
+
+    // First index whose key is >= the anchor (the anchor row's own position, if
+    // present) and the first index whose key is > the anchor (one past it).
+    let at = |ts: i64, id: &str| rows.partition_point(|r| (r.timestamp_ms, r.id.as_str()) < (ts, id));
+    let after = |ts: i64, id: &str| rows.partition_point(|r| (r.timestamp_ms, r.id.as_str()) <= (ts, id));
+
+    let (window, has_more) = match direction {
+        PageDirection::Latest => {
+            let from = rows.len().saturating_sub(limit);
+            (rows[from..].to_vec(), from > 0)
+        }
+        PageDirection::After => {
+            let from = match anchor {
+                Some((ts, id)) => after(ts, id),
+                None => 0,
+            };
+            let end = (from + limit).min(rows.len());
+            (rows[from..end].to_vec(), end < rows.len())
+        }
+        PageDirection::Before => {
+            let to = match anchor {
+                Some((ts, id)) => at(ts, id),
+                None => rows.len(),
+            };
+            let from = to.saturating_sub(limit);
+            (rows[from..to].to_vec(), from > 0)
+        }
+        PageDirection::Around => {
+            // Center on the anchor row and include it. With no cursor there is no
+            // anchor to center on, so fall back to the most recent window.
+            let center = match anchor {
+                Some((ts, id)) => at(ts, id),
+                None => rows.len(),
+            };
+            let before = limit / 2;
+            // Slide the window back if it would run off the end, so we always
+            // return a contiguous, in-order run of up to `limit` rows.
+            let end = (center.saturating_sub(before) + limit).min(rows.len());
+            let from = end.saturating_sub(limit);
+            (rows[from..end].to_vec(), from > 0 || end < rows.len())
+        }
+    };
+
+    let start = window.first().map(|r| MessageCursor {
+        timestamp_ms: r.timestamp_ms,
+        message_id: r.id.clone(),
+    });
+    let end = window.last().map(|r| MessageCursor {
+        timestamp_ms: r.timestamp_ms,
+        message_id: r.id.clone(),
+    });
+
+    Ok(MessagePage { messages: window, has_more, start, end })
+}
@@ -1,28 +1,74 @@
 //! Logging and panic handling
 
-use std::collections::VecDeque;
-use std::sync::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
 use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+use crate::types::AnkurahError;
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
 
-use crate::types::LogEntry;
+use crate::types::{LogEntry, PanicReport};
 
 // =============================================================================
 // Panic Handling
 // =============================================================================
 
-static PANIC_LOG: OnceCell<Mutex<Vec<String>>> = OnceCell::new();
+static PANIC_LOG: OnceCell<Mutex<Vec<PanicReport>>> = OnceCell::new();
 const MAX_PANICS: usize = 100;
+/// Number of trailing log lines captured into each crash record.
+const PANIC_LOG_TAIL: usize = 50;
 
-fn panic_storage() -> &'static Mutex<Vec<String>> {
+fn panic_storage() -> &'static Mutex<Vec<PanicReport>> {
     PANIC_LOG.get_or_init(|| Mutex::new(Vec::new()))
 }
 
+/// Snapshot the tail of the log buffer for inclusion in a crash record.
+///
+/// Uses a non-blocking `try_read` so a held or poisoned lock can never turn a
+/// panic into a deadlock; returns the last `PANIC_LOG_TAIL` entries, newest-last.
+fn snapshot_recent_logs() -> Vec<LogEntry> {
+    match LOG_BUFFER.try_read() {
+        Ok(buf) => {
+            let start = buf.len().saturating_sub(PANIC_LOG_TAIL);
+            buf.iter().skip(start).cloned().collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Render a crash record the way `get_last_panic` exposes it: header, backtrace,
+/// then the pre-crash log tail, newest-last.
+fn format_panic(report: &PanicReport) -> String {
+    let mut out = format!(
+        "PANIC at {}: {}\n{}",
+        report.location, report.message, report.backtrace
+    );
+    if !report.recent_logs.is_empty() {
+        out.push_str("\n--- recent logs ---\n");
+        for entry in &report.recent_logs {
+            out.push_str(&format!(
+                "[{}] {} {}: {}\n",
+                entry.timestamp_ms, entry.level, entry.target, entry.message
+            ));
+        }
+    }
+    out
+}
+
 #[ctor::ctor]
 fn init_panic_hook() {
-    std::panic::set_hook(Box::new(|info| {
+    // Preserve whatever hook was already installed (e.g. the default that aborts
+    // on panic-in-panic) and chain to it after our own logging runs.
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
         let payload = info
             .payload()
             .downcast_ref::<&str>()
@@ -30,21 +76,39 @@ fn init_panic_hook() {
             .or_else(|| info.payload().downcast_ref::<String>().cloned())
             .unwrap_or_else(|| "Unknown panic".to_string());
 
+        // Attribute the panic to its thread so a UI-thread panic is
+        // distinguishable from a sync-worker panic.
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("unnamed");
+        let message = format!("[thread {} ({:?})] {}", thread_name, thread.id(), payload);
+
         let location = info
             .location()
             .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
             .unwrap_or_else(|| "unknown".to_string());
 
-        let msg = format!("PANIC at {}: {}\n{:?}", location, payload, std::backtrace::Backtrace::capture());
+        let report = PanicReport {
+            message,
+            location,
+            backtrace: format!("{:?}", std::backtrace::Backtrace::capture()),
+            recent_logs: snapshot_recent_logs(),
+        };
+
+        let rendered = format_panic(&report);
+
+        // Queue a crash event for later upload. This takes a lock and rewrites
+        // the local pending file but does no network I/O, so the panic path
+        // itself never blocks on the network.
+        enqueue_crash(&report);
 
         if let Ok(mut log) = panic_storage().lock() {
             if log.len() >= MAX_PANICS {
                 log.remove(0);
             }
-            log.push(msg.clone());
+            log.push(report);
         }
 
-        eprintln!("{}", msg);
+        eprintln!("{}", rendered);
         if let Some(dir) = dirs::data_local_dir() {
             let path = dir.join("ankurah").join("panic_log.txt");
             let _ = std::fs::create_dir_all(path.parent().unwrap());
@@ -58,14 +122,273 @@ fn init_panic_hook() {
                         .duration_since(std::time::UNIX_EPOCH)
                         .map(|d| d.as_secs())
                         .unwrap_or(0);
-                    writeln!(f, "=== {} ===\n{}\n", ts, msg)
+                    writeln!(f, "=== {} ===\n{}\n", ts, rendered)
                 });
         }
+
+        // Chain to the previously installed hook (default or otherwise).
+        previous(info);
     }));
 }
 
+// =============================================================================
+// Crash reporting - Sentry-style envelope export and upload
+// =============================================================================
+
+struct CrashConfig {
+    url: String,
+    release: String,
+    environment: String,
+}
+
+static CRASH_CONFIG: RwLock<Option<CrashConfig>> = RwLock::new(None);
+static CRASH_QUEUE: Mutex<VecDeque<CrashEvent>> = Mutex::new(VecDeque::new());
+/// Monotonic counter making each event id unique within a process run.
+static EVENT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone)]
+struct CrashEvent {
+    event_id: String,
+    timestamp_ms: u64,
+    report: PanicReport,
+}
+
+/// Serializable crash envelope POSTed to the reporting backend.
+#[derive(Serialize)]
+struct CrashEnvelope<'a> {
+    event_id: &'a str,
+    timestamp_ms: u64,
+    level: &'static str,
+    release: &'a str,
+    environment: &'a str,
+    message: &'a str,
+    location: &'a str,
+    backtrace: &'a str,
+    logs: Vec<String>,
+}
+
+fn pending_reports_path() -> Option<std::path::PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("ankurah").join("pending_crash_reports.jsonl"))
+}
+
+/// Build a stable, unique event id from the wall clock and a sequence counter.
+fn next_event_id(timestamp_ms: u64) -> String {
+    let seq = EVENT_SEQ.fetch_add(1, Ordering::Relaxed);
+    format!("{:016x}{:08x}", timestamp_ms, seq)
+}
+
+/// Queue a crash event (bounded at `MAX_PANICS`) and persist it so it survives
+/// a restart until uploaded. Called from the panic hook; does no network I/O.
+fn enqueue_crash(report: &PanicReport) {
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let event = CrashEvent {
+        event_id: next_event_id(timestamp_ms),
+        timestamp_ms,
+        report: report.clone(),
+    };
+
+    if let Ok(mut queue) = CRASH_QUEUE.lock() {
+        if queue.len() >= MAX_PANICS {
+            queue.pop_front();
+        }
+        queue.push_back(event);
+        persist_pending(&queue);
+    }
+}
+
+/// Persisted line for a pending (un-uploaded) crash event.
+#[derive(Serialize, serde::Deserialize)]
+struct PendingEvent {
+    event_id: String,
+    timestamp_ms: u64,
+    message: String,
+    location: String,
+    backtrace: String,
+    logs: Vec<String>,
+}
+
+fn log_lines(report: &PanicReport) -> Vec<String> {
+    report
+        .recent_logs
+        .iter()
+        .map(|e| format!("[{}] {} {}: {}", e.timestamp_ms, e.level, e.target, e.message))
+        .collect()
+}
+
+/// Rewrite the on-disk pending queue to match the in-memory queue.
+fn persist_pending(queue: &VecDeque<CrashEvent>) {
+    let Some(path) = pending_reports_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let mut body = String::new();
+    for event in queue {
+        let pending = PendingEvent {
+            event_id: event.event_id.clone(),
+            timestamp_ms: event.timestamp_ms,
+            message: event.report.message.clone(),
+            location: event.report.location.clone(),
+            backtrace: event.report.backtrace.clone(),
+            logs: log_lines(&event.report),
+        };
+        if let Ok(line) = serde_json::to_string(&pending) {
+            body.push_str(&line);
+            body.push('\n');
+        }
+    }
+    let _ = std::fs::write(&path, body);
+}
+
+/// Re-queue any crash events that were persisted but never uploaded (e.g. the
+/// process died before `flush_crash_reports` ran), de-duplicating by event id.
+fn requeue_persisted() {
+    let Some(path) = pending_reports_path() else { return };
+    let Ok(contents) = std::fs::read_to_string(&path) else { return };
+    let Ok(mut queue) = CRASH_QUEUE.lock() else { return };
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(pending) = serde_json::from_str::<PendingEvent>(line) else { continue };
+        if queue.iter().any(|e| e.event_id == pending.event_id) {
+            continue;
+        }
+        // Only the rendered log lines are persisted; parse them back into entries
+        // so the envelope carries the same lines. Structured fields are not kept.
+        let recent_logs: Vec<LogEntry> = pending.logs.iter().filter_map(|line| {
+            let (ts, rest) = line.strip_prefix('[')?.split_once("] ")?;
+            let (level, rest) = rest.split_once(' ')?;
+            let (target, message) = rest.split_once(": ")?;
+            Some(LogEntry {
+                timestamp_ms: ts.parse().ok()?,
+                level: level.to_string(),
+                target: target.to_string(),
+                message: message.to_string(),
+                fields: HashMap::new(),
+            })
+        }).collect();
+        if queue.len() >= MAX_PANICS {
+            queue.pop_front();
+        }
+        queue.push_back(CrashEvent {
+            event_id: pending.event_id,
+            timestamp_ms: pending.timestamp_ms,
+            report: PanicReport {
+                message: pending.message,
+                location: pending.location,
+                backtrace: pending.backtrace,
+                recent_logs,
+            },
+        });
+    }
+}
+
+/// Configure crash reporting and re-queue any persisted, un-uploaded events.
+///
+/// `dsn_or_url` is the collector endpoint; `release`/`environment` tag every
+/// event so the backend can group them.
+#[uniffi::export]
+pub fn configure_crash_reporting(dsn_or_url: String, release: String, environment: String) {
+    *CRASH_CONFIG.write().unwrap() = Some(CrashConfig {
+        url: dsn_or_url,
+        release,
+        environment,
+    });
+    requeue_persisted();
+}
+
+/// Drain the queued crash events and upload each as a JSON envelope with
+/// retry/backoff. Runs off the panic path so network I/O never blocks a crash.
+#[uniffi::export]
+pub async fn flush_crash_reports() -> Result<(), AnkurahError> {
+    let (url, release, environment) = {
+        let guard = CRASH_CONFIG.read().unwrap();
+        let cfg = guard.as_ref().ok_or(AnkurahError::Internal {
+            message: "crash reporting not configured".to_string(),
+        })?;
+        (cfg.url.clone(), cfg.release.clone(), cfg.environment.clone())
+    };
+
+    let pending: Vec<CrashEvent> = {
+        let queue = CRASH_QUEUE.lock().unwrap();
+        queue.iter().cloned().collect()
+    };
+
+    let client = reqwest::Client::new();
+    for event in pending {
+        let envelope = CrashEnvelope {
+            event_id: &event.event_id,
+            timestamp_ms: event.timestamp_ms,
+            level: "fatal",
+            release: &release,
+            environment: &environment,
+            message: &event.report.message,
+            location: &event.report.location,
+            backtrace: &event.report.backtrace,
+            logs: log_lines(&event.report),
+        };
+
+        if upload_with_backoff(&client, &url, &envelope).await {
+            // Remove the uploaded event from the queue and rewrite the sidecar.
+            if let Ok(mut queue) = CRASH_QUEUE.lock() {
+                queue.retain(|e| e.event_id != event.event_id);
+                persist_pending(&queue);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// POST one envelope, retrying with 250ms-doubling backoff (up to 4 attempts).
+async fn upload_with_backoff(
+    client: &reqwest::Client,
+    url: &str,
+    envelope: &CrashEnvelope<'_>,
+) -> bool {
+    const MAX_ATTEMPTS: u32 = 4;
+    let mut delay_ms = 250u64;
+    for attempt in 0..MAX_ATTEMPTS {
+        match client.post(url).json(envelope).send().await {
+            Ok(resp) if resp.status().is_success() => return true,
+            Ok(resp) => tracing::warn!("crash upload rejected ({}): {}", envelope.event_id, resp.status()),
+            Err(e) => tracing::warn!("crash upload failed ({}): {}", envelope.event_id, e),
+        }
+        if attempt + 1 < MAX_ATTEMPTS {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            delay_ms = (delay_ms * 2).min(30_000);
+        }
+    }
+    false
+}
+
+/// Spawn a named thread whose panics are recorded with that name.
+///
+/// Because the panic hook reads `thread::current().name()`, naming the thread
+/// is enough for `get_last_panic` to tell a sync-worker panic from a UI-thread
+/// one. Use this for threads the crate spawns itself.
+pub fn spawn_named<F>(name: impl Into<String>, f: F) -> std::thread::JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    std::thread::Builder::new()
+        .name(name.into())
+        .spawn(f)
+        .expect("failed to spawn named thread")
+}
+
 #[uniffi::export]
 pub fn get_last_panic() -> Option<String> {
+    panic_storage()
+        .lock()
+        .ok()
+        .and_then(|g| g.last().map(format_panic))
+}
+
+/// Like `get_last_panic`, but returns the structured record including the
+/// pre-crash log tail so the app can surface a full trace.
+#[uniffi::export]
+pub fn get_last_panic_with_logs() -> Option<PanicReport> {
     panic_storage().lock().ok().and_then(|g| g.last().cloned())
 }
 
@@ -76,12 +399,33 @@ pub fn get_last_panic() -> Option<String> {
 static LOG_BUFFER: RwLock<VecDeque<LogEntry>> = RwLock::new(VecDeque::new());
 const MAX_LOGS: usize = 1000;
 
+/// Opt-in push listener: receives each `LogEntry` as it is logged, so a screen
+/// can re-render immediately instead of polling `get_buffered_logs`.
+#[uniffi::export(callback_interface)]
+pub trait LogListener: Send + Sync {
+    fn on_log(&self, entry: LogEntry);
+}
+
+static LOG_LISTENER: RwLock<Option<Arc<dyn LogListener>>> = RwLock::new(None);
+
+/// Register a listener to receive log entries as they happen.
+#[uniffi::export]
+pub fn set_log_listener(listener: Box<dyn LogListener>) {
+    *LOG_LISTENER.write().unwrap() = Some(Arc::from(listener));
+}
+
+/// Remove the push listener; buffering via `get_buffered_logs` continues.
+#[uniffi::export]
+pub fn clear_log_listener() {
+    *LOG_LISTENER.write().unwrap() = None;
+}
+
 struct BufferLogLayer;
 
 impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for BufferLogLayer {
     fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
-        let mut message = String::new();
-        event.record(&mut MessageVisitor(&mut message));
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
 
         let entry = LogEntry {
             timestamp_ms: std::time::SystemTime::now()
@@ -90,51 +434,123 @@ impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for BufferLogLayer {
                 .unwrap_or(0),
             level: event.metadata().level().to_string(),
             target: event.metadata().target().to_string(),
-            message,
+            message: visitor.message,
+            fields: visitor.fields,
         };
 
+        // Append to the bounded buffer, releasing the write guard before we run
+        // any callback so a logging listener can never deadlock the buffer.
         if let Ok(mut buf) = LOG_BUFFER.write() {
             if buf.len() >= MAX_LOGS {
                 buf.pop_front();
             }
-            buf.push_back(entry);
+            buf.push_back(entry.clone());
+        }
+
+        // Clone out the listener under a short-lived read lock, then call it
+        // with the lock released to avoid reentrancy if the callback logs.
+        let listener = LOG_LISTENER.read().ok().and_then(|g| g.clone());
+        if let Some(listener) = listener {
+            listener.on_log(entry);
         }
     }
 }
 
-struct MessageVisitor<'a>(&'a mut String);
+/// Collects the human `message` and the structured key/value fields separately,
+/// so consumers get both the message and the individual fields for filtering.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: HashMap<String, String>,
+}
 
-impl<'a> tracing::field::Visit for MessageVisitor<'a> {
+impl tracing::field::Visit for MessageVisitor {
     fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
         if field.name() == "message" {
-            *self.0 = format!("{:?}", value);
-        } else if self.0.is_empty() {
-            *self.0 = format!("{}={:?}", field.name(), value);
+            self.message = format!("{:?}", value);
         } else {
-            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
         }
     }
 
     fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
         if field.name() == "message" {
-            *self.0 = value.to_string();
-        } else if self.0.is_empty() {
-            *self.0 = format!("{}={}", field.name(), value);
+            self.message = value.to_string();
         } else {
-            self.0.push_str(&format!(" {}={}", field.name(), value));
+            self.fields.insert(field.name().to_string(), value.to_string());
         }
     }
 }
 
+/// Reload handle for the global level filter, so `set_log_level` can raise or
+/// lower verbosity at runtime without a restart.
+static LEVEL_RELOAD: OnceCell<reload::Handle<LevelFilter, Registry>> = OnceCell::new();
+
+/// Boxed layer type carried by the telemetry reload slot. Boxing lets us swap a
+/// no-op in for the OTLP exporter only once `init_telemetry` has an endpoint.
+type TelemetryLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Reload handle for the optional OpenTelemetry export layer. Starts empty and
+/// is filled in by `init_telemetry` once a collector endpoint is known.
+static TELEMETRY_RELOAD: OnceCell<reload::Handle<Option<TelemetryLayer>, Registry>> = OnceCell::new();
+
+/// Coarse numeric rank of a level name, for threshold filtering in
+/// `get_recent_logs`. Unknown names fall back to INFO.
+fn level_rank(level: &str) -> u8 {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => 0,
+        "DEBUG" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        "ERROR" => 4,
+        _ => 2,
+    }
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => Some(LevelFilter::TRACE),
+        "DEBUG" => Some(LevelFilter::DEBUG),
+        "INFO" => Some(LevelFilter::INFO),
+        "WARN" => Some(LevelFilter::WARN),
+        "ERROR" => Some(LevelFilter::ERROR),
+        "OFF" => Some(LevelFilter::OFF),
+        _ => None,
+    }
+}
+
+/// Change the global log level at runtime (e.g. bump to DEBUG/TRACE for a
+/// debugging session). Accepts TRACE/DEBUG/INFO/WARN/ERROR/OFF.
+#[uniffi::export]
+pub fn set_log_level(level: String) -> Result<(), AnkurahError> {
+    let filter = parse_level(&level).ok_or_else(|| AnkurahError::Internal {
+        message: format!("unknown log level: {}", level),
+    })?;
+    let handle = LEVEL_RELOAD.get().ok_or(AnkurahError::NotInitialized)?;
+    handle
+        .reload(filter)
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })
+}
+
 /// Initialize the tracing subscriber. Call once at startup.
 #[uniffi::export]
 pub fn init_logging() {
     use std::sync::Once;
     static INIT: Once = Once::new();
     INIT.call_once(|| {
+        let (filter, filter_handle) = reload::Layer::new(LevelFilter::INFO);
+        let _ = LEVEL_RELOAD.set(filter_handle);
+
+        // Start with no telemetry layer; `init_telemetry` swaps in the OTLP
+        // exporter later once an endpoint is configured.
+        let (telemetry, telemetry_handle) = reload::Layer::new(None::<TelemetryLayer>);
+        let _ = TELEMETRY_RELOAD.set(telemetry_handle);
+
         tracing_subscriber::registry()
+            .with(filter)
             .with(BufferLogLayer)
-            .with(tracing_subscriber::filter::LevelFilter::INFO)
+            .with(telemetry)
             .init();
         tracing::info!("Logging initialized");
     });
@@ -145,3 +561,60 @@ pub fn init_logging() {
 pub fn get_buffered_logs() -> Vec<LogEntry> {
     LOG_BUFFER.write().ok().map(|mut b| b.drain(..).collect()).unwrap_or_default()
 }
+
+/// Return the most recent buffered logs at or above `min_level` without draining
+/// them, newest-last, capped at `max` entries (0 means no cap). Unlike
+/// `get_buffered_logs`, the buffer is left intact so a debug screen can bound how
+/// much backlog it pulls and inspect recent history repeatedly.
+#[uniffi::export]
+pub fn get_recent_logs(max: u32, min_level: String) -> Vec<LogEntry> {
+    let threshold = level_rank(&min_level);
+    LOG_BUFFER
+        .read()
+        .ok()
+        .map(|b| {
+            let matching = b.iter().filter(|e| level_rank(&e.level) >= threshold);
+            let mut entries: Vec<LogEntry> = if max == 0 {
+                matching.cloned().collect()
+            } else {
+                // Keep the newest `max`: take from the tail, then restore order.
+                let mut tail: Vec<LogEntry> =
+                    matching.rev().take(max as usize).cloned().collect();
+                tail.reverse();
+                tail
+            };
+            entries.shrink_to_fit();
+            entries
+        })
+        .unwrap_or_default()
+}
+
+/// Enable OpenTelemetry OTLP export to `endpoint`, attaching the exporter to the
+/// already-running subscriber via the telemetry reload slot.
+///
+/// The OTLP batch exporter spawns a background task on construction, so it must
+/// be installed from within the Tokio runtime; we enter the shared runtime for
+/// the duration of the call so this doesn't panic when invoked from a UniFFI
+/// worker thread that has no reactor of its own.
+#[uniffi::export]
+pub fn init_telemetry(endpoint: String) -> Result<(), AnkurahError> {
+    let handle = TELEMETRY_RELOAD.get().ok_or(AnkurahError::NotInitialized)?;
+
+    let runtime = crate::init::runtime().ok_or(AnkurahError::NotInitialized)?;
+    let _guard = runtime.enter();
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })?;
+
+    let layer: TelemetryLayer = Box::new(tracing_opentelemetry::layer().with_tracer(tracer));
+    handle
+        .reload(Some(layer))
+        .map_err(|e| AnkurahError::Internal { message: e.to_string() })
+}
@@ -13,6 +13,8 @@ pub enum AnkurahError {
     AlreadyInitialized,
     #[error("Internal error: {message}")]
     Internal { message: String },
+    #[error("Authentication failed: {message}")]
+    Auth { message: String },
 }
 
 /// A single log entry
@@ -22,4 +24,105 @@ pub struct LogEntry {
     pub level: String,
     pub target: String,
     pub message: String,
+    /// Structured key/value fields from the event, excluding `message`, so
+    /// consumers can filter/display them instead of parsing the message string.
+    pub fields: std::collections::HashMap<String, String>,
+}
+
+/// A captured panic together with the log context leading up to it.
+#[derive(uniffi::Record, Clone)]
+pub struct PanicReport {
+    pub message: String,
+    pub location: String,
+    pub backtrace: String,
+    /// Tail of the log buffer at panic time, oldest-first (newest-last).
+    pub recent_logs: Vec<LogEntry>,
+}
+
+/// Direction for windowed message paging (CHATHISTORY-style).
+///
+/// - `Before`/`After`: page relative to `cursor`.
+/// - `Latest`: most recent `limit` messages (cursor ignored).
+/// - `Around`: split `limit` half-before / half-after the cursor anchor.
+#[derive(uniffi::Enum)]
+pub enum PageDirection {
+    Before,
+    After,
+    Latest,
+    Around,
+}
+
+/// Stable cursor into a room's message timeline.
+///
+/// `(timestamp_ms, message_id)` together give a total order even when two
+/// messages share a timestamp, so adjacent pages never skip or repeat a row.
+#[derive(uniffi::Record, Clone)]
+pub struct MessageCursor {
+    pub timestamp_ms: i64,
+    pub message_id: String,
+}
+
+/// Ephemeral presence state for a user in a room. Never persisted to Sled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PresenceState {
+    Online,
+    Away,
+    Typing,
+}
+
+impl PresenceState {
+    /// Compact wire tag used in ephemeral presence beacons.
+    pub fn as_tag(self) -> &'static str {
+        match self {
+            PresenceState::Online => "online",
+            PresenceState::Away => "away",
+            PresenceState::Typing => "typing",
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "online" => Some(PresenceState::Online),
+            "away" => Some(PresenceState::Away),
+            "typing" => Some(PresenceState::Typing),
+            _ => None,
+        }
+    }
+
+    /// Time-to-live for this state. Typing clears quickly; presence lingers.
+    pub fn ttl_ms(self) -> i64 {
+        match self {
+            PresenceState::Typing => 5_000,
+            PresenceState::Online | PresenceState::Away => 30_000,
+        }
+    }
+}
+
+/// A room the current user belongs to, returned by `fetch_my_rooms`.
+#[derive(uniffi::Record, Clone)]
+pub struct RoomItem {
+    pub id: String,
+    pub name: String,
+}
+
+/// A single message row returned by `fetch_message_page`.
+#[derive(uniffi::Record, Clone)]
+pub struct MessageItem {
+    pub id: String,
+    pub text: String,
+    pub timestamp_ms: i64,
+}
+
+/// One window of a room's messages plus the cursors needed to page around it.
+///
+/// `start`/`end` are the cursors of the first and last returned rows (in total
+/// order); pass `end` back with `After` to live-tail or `start` with `Before`
+/// to scroll back. `has_more` is true when rows exist beyond the window in the
+/// requested direction.
+#[derive(uniffi::Record)]
+pub struct MessagePage {
+    pub messages: Vec<MessageItem>,
+    pub has_more: bool,
+    pub start: Option<MessageCursor>,
+    pub end: Option<MessageCursor>,
 }
@@ -68,7 +68,13 @@ async fn ensure_seed_data(node: &Node<SledStorageEngine, PermissiveAgent>) -> Re
     let user_id = if users.is_empty() {
         info!("Creating 'SeedBot' user");
         let trx = context.begin();
-        let user = trx.create(&User { display_name: "SeedBot".to_string() }).await?;
+        let user = trx
+            .create(&User {
+                display_name: "SeedBot".to_string(),
+                // Bot account: no usable password, cannot log in.
+                password_hash: String::new(),
+            })
+            .await?;
         let id = user.id().to_string();
         trx.commit().await?;
         info!("'SeedBot' user created with ID: {}", id);